@@ -0,0 +1,17 @@
+use collab::preclude::*;
+
+/// Stores the block tree of a [Document] keyed by block id.
+#[derive(Clone)]
+pub struct BlockMap {
+    container: MapRefWrapper,
+}
+
+impl BlockMap {
+    pub fn new(container: MapRefWrapper) -> Self {
+        Self { container }
+    }
+
+    pub fn to_json(&self) -> String {
+        self.container.to_json()
+    }
+}
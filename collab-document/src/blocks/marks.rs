@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use collab::preclude::*;
+
+use crate::blocks::TextMap;
+
+/// A single formatting run, e.g. "bold from char 3 to char 8".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mark {
+    pub key: String,
+    pub value: Any,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl TextMap {
+    /// Applies `attributes` to `[index, index + len)` of `text_id`.
+    pub fn format_with_txn(
+        &self,
+        txn: &mut TransactionMut,
+        text_id: &str,
+        index: u32,
+        len: u32,
+        attributes: HashMap<String, Any>,
+    ) {
+        if let Some(text_ref) = self.get_text_ref(txn, text_id) {
+            let attrs: Attrs = attributes.into_iter().map(|(k, v)| (k.into(), v)).collect();
+            text_ref.format(txn, index, len, attrs);
+        }
+    }
+
+    /// Clears `key` over `[index, index + len)`.
+    pub fn unmark(&self, txn: &mut TransactionMut, text_id: &str, index: u32, len: u32, key: &str) {
+        if let Some(text_ref) = self.get_text_ref(txn, text_id) {
+            let attrs: Attrs = [(key.into(), Any::Null)].into_iter().collect();
+            text_ref.format(txn, index, len, attrs);
+        }
+    }
+
+    /// Returns the formatting attributes covering `index`, merging into
+    /// adjacent diff chunks that carry the same key/value so a run spanning
+    /// several separate insert ops (e.g. two `insert` calls later formatted
+    /// as one bold range) is reported as a single non-redundant span.
+    pub fn marks_at<T: ReadTxn>(&self, txn: &T, text_id: &str, index: u32) -> Vec<Mark> {
+        let text_ref = match self.get_text_ref(txn, text_id) {
+            Some(text_ref) => text_ref,
+            None => return vec![],
+        };
+
+        let mut chunks = vec![];
+        let mut pos = 0u32;
+        for diff in text_ref.diff(txn, YChange::identity) {
+            let chunk_len = diff.insert.len() as u32;
+            let end = pos + chunk_len;
+            chunks.push((pos, end, diff.attributes));
+            pos = end;
+        }
+
+        let chunk_index = match chunks
+            .iter()
+            .position(|(start, end, _)| index >= *start && index < *end)
+        {
+            Some(chunk_index) => chunk_index,
+            None => return vec![],
+        };
+
+        chunks[chunk_index]
+            .2
+            .iter()
+            .filter(|(_, value)| !matches!(value, Any::Null))
+            .map(|(key, value)| {
+                let mut start = chunks[chunk_index].0;
+                let mut end = chunks[chunk_index].1;
+
+                let mut i = chunk_index;
+                while i > 0 && chunks[i - 1].2.get(key) == Some(value) {
+                    i -= 1;
+                    start = chunks[i].0;
+                }
+                let mut j = chunk_index;
+                while j + 1 < chunks.len() && chunks[j + 1].2.get(key) == Some(value) {
+                    j += 1;
+                    end = chunks[j].1;
+                }
+
+                Mark {
+                    key: key.to_string(),
+                    value: value.clone(),
+                    start,
+                    end,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,7 @@
+mod block_map;
+mod marks;
+mod text_map;
+
+pub use block_map::*;
+pub use marks::*;
+pub use text_map::*;
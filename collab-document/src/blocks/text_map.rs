@@ -0,0 +1,39 @@
+use collab::preclude::*;
+
+/// Stores the rich text nodes of a [Document], keyed by text id.
+///
+/// Each entry is a yrs `Text` so that concurrent edits merge character by
+/// character instead of clobbering the whole string.
+#[derive(Clone)]
+pub struct TextMap {
+    container: MapRefWrapper,
+}
+
+impl TextMap {
+    pub fn new(container: MapRefWrapper) -> Self {
+        Self { container }
+    }
+
+    pub fn create_text(&self, txn: &mut TransactionMut, text_id: &str) {
+        self.container.insert_text_with_txn(txn, text_id);
+    }
+
+    pub fn delete_text(&self, txn: &mut TransactionMut, text_id: &str) {
+        self.container.remove(txn, text_id);
+    }
+
+    pub fn insert(&self, txn: &mut TransactionMut, text_id: &str, index: u32, content: &str) {
+        if let Some(text_ref) = self.get_text_ref(txn, text_id) {
+            text_ref.insert(txn, index, content);
+        }
+    }
+
+    pub fn get_str<T: ReadTxn>(&self, txn: &T, text_id: &str) -> Option<String> {
+        let text_ref = self.container.get(txn, text_id)?.to_ytext()?;
+        Some(text_ref.get_string(txn))
+    }
+
+    pub(crate) fn get_text_ref<T: ReadTxn>(&self, txn: &T, text_id: &str) -> Option<TextRef> {
+        self.container.get(txn, text_id)?.to_ytext()
+    }
+}
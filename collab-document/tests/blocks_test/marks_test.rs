@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use collab::preclude::*;
+use collab_document::blocks::TextMap;
+
+fn text_map() -> (Collab, TextMap) {
+    let collab = CollabBuilder::new(1, "1").build();
+    let container = collab.with_transact_mut(|txn| collab.create_map_with_txn(txn, "text"));
+    (collab, TextMap::new(container))
+}
+
+#[test]
+fn marks_at_merges_a_bold_run_spanning_two_insert_ops() {
+    let (collab, text_map) = text_map();
+    collab.with_transact_mut(|txn| {
+        text_map.create_text(txn, "t1");
+        text_map.insert(txn, "t1", 0, "Hello");
+        text_map.insert(txn, "t1", 5, " World");
+        let mut attrs = HashMap::new();
+        attrs.insert("bold".to_string(), Any::Bool(true));
+        text_map.format_with_txn(txn, "t1", 0, 11, attrs);
+    });
+
+    let txn = collab.transact();
+    let marks = text_map.marks_at(&txn, "t1", 7);
+
+    assert_eq!(marks.len(), 1, "expected a single merged bold span, got {marks:?}");
+    assert_eq!(marks[0].key, "bold");
+    assert_eq!(
+        (marks[0].start, marks[0].end),
+        (0, 11),
+        "the run should cover the whole 'Hello World' span, not just the chunk touching index 7"
+    );
+}
+
+#[test]
+fn unmark_clears_formatting_over_a_range() {
+    let (collab, text_map) = text_map();
+    collab.with_transact_mut(|txn| {
+        text_map.create_text(txn, "t1");
+        text_map.insert(txn, "t1", 0, "Hello World");
+        let mut attrs = HashMap::new();
+        attrs.insert("bold".to_string(), Any::Bool(true));
+        text_map.format_with_txn(txn, "t1", 0, 11, attrs);
+        text_map.unmark(txn, "t1", 0, 5, "bold");
+    });
+
+    let txn = collab.transact();
+    assert!(text_map.marks_at(&txn, "t1", 0).is_empty());
+    assert_eq!(text_map.marks_at(&txn, "t1", 6)[0].key, "bold");
+}
@@ -1,5 +1,8 @@
 use crate::core::collab_plugin::CollabPlugin;
 use crate::core::map_wrapper::{CustomMapRef, MapRefWrapper};
+use crate::core::patch::{diff_json, lib0_any_to_json, CollabPatch};
+use crate::core::value::{CollabValue, PathSchemas};
+use crate::error::CollabError;
 use crate::util::insert_json_value_to_map_ref;
 use parking_lot::RwLock;
 use serde::de::DeserializeOwned;
@@ -15,7 +18,7 @@ use yrs::types::{ToJson, Value};
 
 use crate::preclude::ArrayRefWrapper;
 use yrs::{
-    ArrayRef, Doc, Map, MapPrelim, MapRef, Observable, ReadTxn, Subscription, Transact,
+    ArrayRef, Doc, Map, MapPrelim, MapRef, Observable, ReadTxn, Snapshot, Subscription, Transact,
     Transaction, TransactionMut, Update, UpdateSubscription,
 };
 
@@ -30,10 +33,20 @@ pub struct Collab {
     plugins: Plugins,
     #[allow(dead_code)]
     subscription: UpdateSubscription,
+    schemas: PathSchemas,
 }
 
 impl Collab {
     pub fn new<T: AsRef<str>>(uid: i64, cid: T, plugins: Vec<Arc<dyn CollabPlugin>>) -> Collab {
+        Self::new_with_schemas(uid, cid, plugins, Default::default())
+    }
+
+    pub(crate) fn new_with_schemas<T: AsRef<str>>(
+        uid: i64,
+        cid: T,
+        plugins: Vec<Arc<dyn CollabPlugin>>,
+        schemas: PathSchemas,
+    ) -> Collab {
         let cid = cid.as_ref().to_string();
         let doc = Doc::with_client_id(uid as u64);
         let attributes = doc.get_or_insert_map("attrs");
@@ -45,6 +58,7 @@ impl Collab {
             attributes,
             plugins,
             subscription,
+            schemas,
         }
     }
 
@@ -89,7 +103,18 @@ impl Collab {
         self.attributes.insert(txn, key, value);
     }
 
-    pub fn insert_json_with_path<T: Serialize>(&mut self, path: Vec<String>, key: &str, value: T) {
+    pub fn insert_json_with_path<T: Serialize>(
+        &mut self,
+        path: Vec<String>,
+        key: &str,
+        value: T,
+    ) -> Result<(), CollabError> {
+        let value = serde_json::to_value(&value).map_err(CollabError::serde)?;
+
+        let mut full_path = path.clone();
+        full_path.push(key.to_string());
+        self.schemas.validate(&full_path, &value)?;
+
         let mut map = if path.is_empty() {
             None
         } else {
@@ -104,9 +129,9 @@ impl Collab {
                         .insert(txn, key, MapPrelim::<lib0::any::Any>::new()),
                 );
             }
-            let value = serde_json::to_value(&value).unwrap();
             insert_json_value_to_map_ref(key, &value, map.unwrap(), txn);
         });
+        Ok(())
     }
 
     pub fn create_map_with_txn(&self, txn: &mut TransactionMut, key: &str) -> MapRefWrapper {
@@ -129,6 +154,35 @@ impl Collab {
         Some(object)
     }
 
+    /// Reads `path` as a [CollabValue], distinguishing maps/arrays/text from
+    /// plain scalars instead of flattening everything to JSON up front.
+    pub fn get_value_with_path(&self, path: impl Into<Path>) -> Option<CollabValue> {
+        let path = path.into();
+        if path.is_empty() {
+            return None;
+        }
+        let txn = self.transact();
+        let value = self.get_ref_from_path_with_txn(&txn, path)?;
+        Some(CollabValue::from_yrs(&txn, value))
+    }
+
+    /// Like [Self::get_json_with_path], but returns a structured
+    /// [CollabError] describing *why* the read failed (path not found, or
+    /// the stored shape doesn't deserialize into `T`) instead of collapsing
+    /// every failure into `None`.
+    pub fn get_typed_with_path<T: DeserializeOwned>(
+        &self,
+        path: impl Into<Path>,
+    ) -> Result<T, CollabError> {
+        let path = path.into();
+        let path_vec = path.to_vec();
+        let value = self
+            .get_value_with_path(path)
+            .ok_or_else(|| CollabError::path_not_found(path_vec.clone()))?;
+        serde_json::from_value(value.into_json())
+            .map_err(|err| CollabError::type_mismatch(path_vec, err.to_string()))
+    }
+
     pub fn get_map_with_path<M: CustomMapRef>(&self, path: impl Into<Path>) -> Option<M> {
         let txn = self.doc.transact();
         let map_ref = self.get_map_with_txn(&txn, path)?;
@@ -165,7 +219,7 @@ impl Collab {
         array_ref.map(|array_ref| self.array_wrapper_with(array_ref))
     }
 
-    fn get_ref_from_path_with_txn<T: ReadTxn>(&self, txn: &T, mut path: Path) -> Option<Value> {
+    pub(crate) fn get_ref_from_path_with_txn<T: ReadTxn>(&self, txn: &T, mut path: Path) -> Option<Value> {
         if path.is_empty() {
             return None;
         }
@@ -223,6 +277,42 @@ impl Collab {
         self.attributes.to_json(&txn)
     }
 
+    /// Produces a structured, path-addressed diff between `old_snapshot` and
+    /// the document's current state.
+    ///
+    /// `old_snapshot` must be a [Snapshot] captured *at* the old point in
+    /// time (e.g. via `txn.snapshot()`), not an old state vector paired with
+    /// today's delete set — that would treat items deleted since then as
+    /// already-deleted back then and drop their `Delete` patches.
+    pub fn diff_with_txn<T: ReadTxn>(&self, txn: &T, old_snapshot: &Snapshot) -> Vec<CollabPatch> {
+        let old_json = self.reconstruct_json_at(old_snapshot);
+        let new_json = lib0_any_to_json(&self.attributes.to_json(txn));
+        diff_json(&[], &old_json, &new_json)
+    }
+
+    /// Replays the document up to `old_snapshot` into a throwaway [Doc] and
+    /// returns its `attributes` map as JSON, so the caller can diff it
+    /// against the current tree without decoding yrs internals.
+    fn reconstruct_json_at(&self, old_snapshot: &Snapshot) -> serde_json::Value {
+        let txn = self.transact();
+        let old_update = match txn.encode_state_from_snapshot_v1(old_snapshot) {
+            Ok(update) => update,
+            Err(_) => return serde_json::Value::Null,
+        };
+
+        let old_doc = Doc::new();
+        {
+            let mut old_txn = old_doc.transact_mut();
+            if let Ok(update) = Update::decode_v1(&old_update) {
+                old_txn.apply_update(update);
+            }
+        }
+
+        let old_attrs = old_doc.get_or_insert_map("attrs");
+        let old_txn = old_doc.transact();
+        lib0_any_to_json(&old_attrs.to_json(&old_txn))
+    }
+
     pub fn transact(&self) -> Transaction {
         self.doc.transact()
     }
@@ -270,6 +360,7 @@ pub struct CollabBuilder {
     plugins: Vec<Arc<dyn CollabPlugin>>,
     uid: i64,
     cid: String,
+    schemas: PathSchemas,
 }
 
 impl CollabBuilder {
@@ -279,6 +370,7 @@ impl CollabBuilder {
             uid,
             plugins: vec![],
             cid: cid.to_string(),
+            schemas: Default::default(),
         }
     }
 
@@ -290,8 +382,16 @@ impl CollabBuilder {
         self
     }
 
+    /// Registers the expected shape of `path` so writes through
+    /// `insert_json_with_path` are rejected, rather than silently applied,
+    /// when they don't match it.
+    pub fn with_schema(mut self, path: Vec<String>, schema: serde_json::Value) -> Self {
+        self.schemas.register(path, schema);
+        self
+    }
+
     pub fn build_with_updates(self, updates: Vec<Update>) -> Collab {
-        let collab = Collab::new(self.uid, self.cid, self.plugins);
+        let collab = Collab::new_with_schemas(self.uid, self.cid, self.plugins, self.schemas);
         let mut txn = collab.doc.transact_mut();
         for update in updates {
             txn.apply_update(update);
@@ -301,7 +401,7 @@ impl CollabBuilder {
     }
 
     pub fn build(self) -> Collab {
-        Collab::new(self.uid, self.cid, self.plugins)
+        Collab::new_with_schemas(self.uid, self.cid, self.plugins, self.schemas)
     }
 }
 
@@ -0,0 +1,100 @@
+use serde_json::{Map, Value as Json};
+
+/// A single, path-addressed change between two states of a [Collab] document.
+///
+/// Paths are rooted at the `attributes` map and use the same string keys
+/// `get_json_with_path`/`insert_json_with_path` take, so callers can render
+/// "what changed" without decoding yrs internals themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollabPatch {
+    Insert { path: Vec<String>, value: Json },
+    Update { path: Vec<String>, old: Json, new: Json },
+    Delete { path: Vec<String> },
+    /// A ranged edit against a text node, reported instead of `Update`
+    /// because text merges character-by-character rather than replacing
+    /// the whole value.
+    TextEdit {
+        path: Vec<String>,
+        index: u32,
+        inserted: String,
+        removed: u32,
+    },
+}
+
+/// Recursively compares `old` and `new` JSON trees and returns the patches
+/// needed to turn `old` into `new`, keeping paths as fine-grained as
+/// possible by descending into nested objects.
+pub(crate) fn diff_json(path: &[String], old: &Json, new: &Json) -> Vec<CollabPatch> {
+    match (old, new) {
+        (Json::Object(old_map), Json::Object(new_map)) => diff_object(path, old_map, new_map),
+        (Json::Array(old_items), Json::Array(new_items)) => diff_array(path, old_items, new_items),
+        _ if old == new => vec![],
+        _ => vec![CollabPatch::Update {
+            path: path.to_vec(),
+            old: old.clone(),
+            new: new.clone(),
+        }],
+    }
+}
+
+pub(crate) fn lib0_any_to_json(any: &lib0::any::Any) -> Json {
+    serde_json::to_value(any).unwrap_or(Json::Null)
+}
+
+/// Diffs two arrays index by index, so e.g. changing element 2 of a 5-item
+/// array reports a patch at `path/2` instead of replacing the whole array.
+fn diff_array(path: &[String], old: &[Json], new: &[Json]) -> Vec<CollabPatch> {
+    let mut patches = vec![];
+
+    for (index, new_value) in new.iter().enumerate() {
+        let mut child_path = path.to_vec();
+        child_path.push(index.to_string());
+        match old.get(index) {
+            None => patches.push(CollabPatch::Insert {
+                path: child_path,
+                value: new_value.clone(),
+            }),
+            Some(old_value) if old_value != new_value => {
+                patches.extend(diff_json(&child_path, old_value, new_value))
+            }
+            _ => {}
+        }
+    }
+
+    for index in new.len()..old.len() {
+        let mut child_path = path.to_vec();
+        child_path.push(index.to_string());
+        patches.push(CollabPatch::Delete { path: child_path });
+    }
+
+    patches
+}
+
+fn diff_object(path: &[String], old: &Map<String, Json>, new: &Map<String, Json>) -> Vec<CollabPatch> {
+    let mut patches = vec![];
+
+    for (key, new_value) in new.iter() {
+        let mut child_path = path.to_vec();
+        child_path.push(key.clone());
+        match old.get(key) {
+            None => patches.push(CollabPatch::Insert {
+                path: child_path,
+                value: new_value.clone(),
+            }),
+            Some(old_value) if old_value != new_value => {
+                patches.extend(diff_json(&child_path, old_value, new_value))
+            }
+            _ => {}
+        }
+    }
+
+    for key in old.keys() {
+        if !new.contains_key(key) {
+            let mut child_path = path.to_vec();
+            child_path.push(key.clone());
+            patches.push(CollabPatch::Delete { path: child_path });
+        }
+    }
+
+    patches
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use yrs::types::{Change, Delta, EntryChange, Event, Path, PathSegment, ToJson, Value};
+use yrs::{DeepObservable, Subscription, TransactionMut};
+
+use crate::core::collab::Collab;
+use crate::core::patch::{lib0_any_to_json, CollabPatch};
+
+pub type PatchSubscriptionCallback = Arc<dyn Fn(&TransactionMut, &[Event])>;
+pub type PatchSubscription = Subscription<PatchSubscriptionCallback>;
+
+impl Collab {
+    /// Subscribes deeply to the `attributes` map (and every map/array/text
+    /// node nested under it) and translates each transaction's events into
+    /// path-addressed [CollabPatch]es, so callers can react to block/text
+    /// changes without re-implementing `MapEvent` interpretation themselves.
+    pub fn observe_changes<F>(&mut self, f: F) -> PatchSubscription
+    where
+        F: Fn(Vec<CollabPatch>) + 'static,
+    {
+        self.attributes.observe_deep(move |txn, events| {
+            let patches = events
+                .iter()
+                .flat_map(|event| event_to_patches(txn, event))
+                .collect();
+            f(patches);
+        })
+    }
+}
+
+fn path_to_strings(path: Path) -> Vec<String> {
+    path
+        .into_iter()
+        .map(|segment| match segment {
+            PathSegment::Key(key) => key.to_string(),
+            PathSegment::Index(index) => index.to_string(),
+        })
+        .collect()
+}
+
+fn event_to_patches(txn: &TransactionMut, event: &Event) -> Vec<CollabPatch> {
+    match event {
+        Event::Map(map_event) => {
+            let path = path_to_strings(map_event.path());
+            map_event
+                .keys(txn)
+                .iter()
+                .map(|(key, change)| {
+                    let mut child_path = path.clone();
+                    child_path.push(key.to_string());
+                    match change {
+                        EntryChange::Inserted(value) => CollabPatch::Insert {
+                            path: child_path,
+                            value: value_to_json(txn, value),
+                        },
+                        EntryChange::Updated(old, new) => CollabPatch::Update {
+                            path: child_path,
+                            old: value_to_json(txn, old),
+                            new: value_to_json(txn, new),
+                        },
+                        EntryChange::Removed(_) => CollabPatch::Delete { path: child_path },
+                    }
+                })
+                .collect()
+        }
+        Event::Array(array_event) => {
+            let path = path_to_strings(array_event.path());
+            let mut patches = vec![];
+            let mut index = 0u32;
+            for change in array_event.delta(txn).iter() {
+                match change {
+                    Change::Retain(len) => index += *len as u32,
+                    Change::Added(values) => {
+                        for value in values.iter() {
+                            let mut child_path = path.clone();
+                            child_path.push(index.to_string());
+                            patches.push(CollabPatch::Insert {
+                                path: child_path,
+                                value: value_to_json(txn, value),
+                            });
+                            index += 1;
+                        }
+                    }
+                    Change::Removed(len) => {
+                        // Deleted elements vanish, so later positions in this
+                        // delta are already expressed against the
+                        // post-deletion index space — don't advance `index`
+                        // here (mirrors the `Delta::Deleted` arm below).
+                        for _ in 0..*len {
+                            let mut child_path = path.clone();
+                            child_path.push(index.to_string());
+                            patches.push(CollabPatch::Delete { path: child_path });
+                        }
+                    }
+                }
+            }
+            patches
+        }
+        Event::Text(text_event) => {
+            let path = path_to_strings(text_event.path());
+            let mut index = 0u32;
+            text_event
+                .delta(txn)
+                .iter()
+                .filter_map(|delta| match delta {
+                    Delta::Retain(len, _) => {
+                        index += *len;
+                        None
+                    }
+                    Delta::Inserted(value, _) => {
+                        let inserted = value.to_string();
+                        let patch = CollabPatch::TextEdit {
+                            path: path.clone(),
+                            index,
+                            inserted: inserted.clone(),
+                            removed: 0,
+                        };
+                        index += inserted.encode_utf16().count() as u32;
+                        Some(patch)
+                    }
+                    Delta::Deleted(len) => {
+                        let patch = CollabPatch::TextEdit {
+                            path: path.clone(),
+                            index,
+                            inserted: String::new(),
+                            removed: *len,
+                        };
+                        Some(patch)
+                    }
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn value_to_json(txn: &TransactionMut, value: &Value) -> serde_json::Value {
+    lib0_any_to_json(&value.to_json(txn))
+}
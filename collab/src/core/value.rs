@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use serde_json::Value as Json;
+use yrs::types::{ToJson, Value};
+use yrs::ReadTxn;
+
+use crate::error::CollabError;
+
+/// A structured view over a stored value that keeps maps, arrays and text
+/// apart instead of collapsing everything to JSON up front.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollabValue {
+    Map(HashMap<String, CollabValue>),
+    Array(Vec<CollabValue>),
+    Text(String),
+    Any(lib0::any::Any),
+}
+
+impl CollabValue {
+    pub(crate) fn from_yrs<T: ReadTxn>(txn: &T, value: Value) -> Self {
+        match value {
+            Value::YMap(map_ref) => CollabValue::Map(
+                map_ref
+                    .iter(txn)
+                    .map(|(key, value)| (key.to_string(), CollabValue::from_yrs(txn, value)))
+                    .collect(),
+            ),
+            Value::YArray(array_ref) => CollabValue::Array(
+                array_ref
+                    .iter(txn)
+                    .map(|value| CollabValue::from_yrs(txn, value))
+                    .collect(),
+            ),
+            Value::YText(text_ref) => CollabValue::Text(text_ref.get_string(txn)),
+            Value::Any(any) => CollabValue::Any(any),
+            other => CollabValue::Any(other.to_json(txn)),
+        }
+    }
+
+    pub fn into_json(self) -> Json {
+        match self {
+            CollabValue::Map(map) => {
+                Json::Object(map.into_iter().map(|(k, v)| (k, v.into_json())).collect())
+            }
+            CollabValue::Array(values) => {
+                Json::Array(values.into_iter().map(CollabValue::into_json).collect())
+            }
+            CollabValue::Text(text) => Json::String(text),
+            CollabValue::Any(any) => serde_json::to_value(any).unwrap_or(Json::Null),
+        }
+    }
+}
+
+/// Per-path schema expectations registered at [crate::core::collab::CollabBuilder]
+/// time, checked before a `insert_json_with_path` write is committed.
+#[derive(Clone, Default)]
+pub(crate) struct PathSchemas(HashMap<Vec<String>, Json>);
+
+impl PathSchemas {
+    pub(crate) fn register(&mut self, path: Vec<String>, schema: Json) {
+        self.0.insert(path, schema);
+    }
+
+    pub(crate) fn validate(&self, path: &[String], value: &Json) -> Result<(), CollabError> {
+        match self.0.get(path) {
+            Some(schema) => validate_shape(path, schema, value),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that `value`'s JSON shape matches `schema`'s, recursing into
+/// object fields that `schema` declares. `schema` values of `null` are
+/// treated as "any shape accepted" for that field.
+fn validate_shape(path: &[String], schema: &Json, value: &Json) -> Result<(), CollabError> {
+    match (schema, value) {
+        (Json::Null, _) => Ok(()),
+        (Json::Object(schema_fields), Json::Object(value_fields)) => {
+            for (key, field_schema) in schema_fields {
+                let mut field_path = path.to_vec();
+                field_path.push(key.clone());
+                match value_fields.get(key) {
+                    Some(field_value) => validate_shape(&field_path, field_schema, field_value)?,
+                    None => return Err(CollabError::missing_field(field_path)),
+                }
+            }
+            Ok(())
+        }
+        (Json::Array(_), Json::Array(_)) => Ok(()),
+        (Json::String(_), Json::String(_)) => Ok(()),
+        (Json::Number(_), Json::Number(_)) => Ok(()),
+        (Json::Bool(_), Json::Bool(_)) => Ok(()),
+        _ => Err(CollabError::type_mismatch(
+            path.to_vec(),
+            format!("expected shape like {schema}, got {value}"),
+        )),
+    }
+}
+
+impl CollabError {
+    pub fn path_not_found(path: Vec<String>) -> Self {
+        CollabError::PathNotFound(path)
+    }
+
+    pub fn missing_field(path: Vec<String>) -> Self {
+        CollabError::MissingField(path)
+    }
+
+    pub fn type_mismatch(path: Vec<String>, reason: String) -> Self {
+        CollabError::TypeMismatch { path, reason }
+    }
+
+    pub fn serde(err: serde_json::Error) -> Self {
+        CollabError::Serde(err)
+    }
+}
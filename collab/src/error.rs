@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CollabError {
+    #[error("path not found: {}", .0.join("/"))]
+    PathNotFound(Vec<String>),
+
+    /// A write's value was missing a field its registered schema requires —
+    /// distinct from [CollabError::PathNotFound], which means nothing is
+    /// stored at a path at all, so callers can't mistake a rejected write for
+    /// an absent read.
+    #[error("missing field required by schema at {}", .0.join("/"))]
+    MissingField(Vec<String>),
+
+    #[error("type mismatch at {}: {reason}", .path.join("/"))]
+    TypeMismatch { path: Vec<String>, reason: String },
+
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
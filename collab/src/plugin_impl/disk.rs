@@ -4,21 +4,105 @@ use crate::error::CollabError;
 use collab_persistence::doc::YrsDoc;
 use collab_persistence::CollabKV;
 
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
 use std::sync::Arc;
-use yrs::TransactionMut;
+use yrs::{Doc, StateVector, Transact, TransactionMut};
+
+/// Default number of appended updates a document can accumulate before the
+/// plugin folds its log into a single consolidated state. Override with
+/// [CollabDiskPlugin::with_compact_threshold].
+const DEFAULT_COMPACT_UPDATE_THRESHOLD: u32 = 500;
 
 #[derive(Clone)]
 pub struct CollabDiskPlugin {
     db: Arc<CollabKV>,
+    compact_threshold: u32,
+    /// Per-`cid` appended-update count since the last compaction, behind its
+    /// own lock so that compacting one document's (synchronous, disk I/O
+    /// bound) log doesn't stall writes to every other document. The outer
+    /// `RwLock` only ever guards inserting a new `cid`'s entry; the inner
+    /// `Mutex` is held across `push_update` *and* a potential compaction for
+    /// that `cid`, so a write can never land in its disk log after `compact`
+    /// has already read it but before it replaces it.
+    pending_updates: Arc<RwLock<HashMap<String, Arc<Mutex<u32>>>>>,
 }
 impl CollabDiskPlugin {
     pub fn new(db: Arc<CollabKV>) -> Result<Self, CollabError> {
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            compact_threshold: DEFAULT_COMPACT_UPDATE_THRESHOLD,
+            pending_updates: Default::default(),
+        })
+    }
+
+    /// Overrides the number of appended updates that triggers compaction.
+    pub fn with_compact_threshold(mut self, threshold: u32) -> Self {
+        self.compact_threshold = threshold;
+        self
     }
 
     pub fn doc(&self) -> YrsDoc {
         self.db.doc()
     }
+
+    /// Returns `cid`'s pending-update lock, creating it if this is the first
+    /// time `cid` has been seen.
+    fn pending_lock(&self, cid: &str) -> Arc<Mutex<u32>> {
+        if let Some(lock) = self.pending_updates.read().get(cid) {
+            return lock.clone();
+        }
+        self.pending_updates
+            .write()
+            .entry(cid.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(0)))
+            .clone()
+    }
+
+    /// Persists a named, full-state snapshot of `cid` alongside its live
+    /// update log so callers can checkpoint a document and roll it back
+    /// later with [Self::restore_snapshot].
+    pub fn save_snapshot(&self, cid: &str, name: &str) -> Result<(), CollabError> {
+        let lock = self.pending_lock(cid);
+        let _guard = lock.lock();
+        let state = self.encode_doc_state(cid)?;
+        self.db.doc().save_snapshot(cid, name, &state)?;
+        Ok(())
+    }
+
+    /// Replaces `cid`'s live state with a previously saved snapshot.
+    pub fn restore_snapshot(&self, cid: &str, name: &str) -> Result<(), CollabError> {
+        let lock = self.pending_lock(cid);
+        let mut count = lock.lock();
+        let state = self.db.doc().load_snapshot(cid, name)?;
+        self.db.doc().replace_updates(cid, state)?;
+        *count = 0;
+        Ok(())
+    }
+
+    /// Loads every update recorded for `cid` into a throwaway [Doc] and
+    /// re-encodes it as a single state, replacing the on-disk log with that
+    /// one consolidated update plus an empty tail. This keeps `did_init`
+    /// from having to replay an ever-growing history for long-lived docs.
+    ///
+    /// Callers must hold `pending_updates`'s lock for `cid` so no update can
+    /// be appended between the read here and the replace.
+    fn compact(&self, cid: &str) -> Result<(), CollabError> {
+        let state = self.encode_doc_state(cid)?;
+        self.db.doc().replace_updates(cid, state)?;
+        Ok(())
+    }
+
+    fn encode_doc_state(&self, cid: &str) -> Result<Vec<u8>, CollabError> {
+        let tmp_doc = Doc::new();
+        {
+            let mut txn = tmp_doc.transact_mut();
+            self.db.doc().load_doc(cid, &mut txn)?;
+        }
+        Ok(tmp_doc
+            .transact()
+            .encode_state_as_update_v1(&StateVector::default()))
+    }
 }
 
 impl CollabPlugin for CollabDiskPlugin {
@@ -32,6 +116,39 @@ impl CollabPlugin for CollabDiskPlugin {
     }
 
     fn did_receive_update(&self, cid: &str, _txn: &TransactionMut, update: &[u8]) {
+        let lock = self.pending_lock(cid);
+        let mut count = lock.lock();
         self.db.doc().push_update(cid, update).unwrap();
+
+        *count += 1;
+        if should_compact(*count, self.compact_threshold) && self.compact(cid).is_ok() {
+            *count = 0;
+        }
+    }
+}
+
+fn should_compact(pending_update_count: u32, threshold: u32) -> bool {
+    pending_update_count >= threshold
+}
+
+// `compact`/`save_snapshot`/`restore_snapshot` need a real `CollabKV`, which
+// this tree doesn't vendor, so only the pure threshold decision is unit
+// tested here; the log-replacement path wants an integration test alongside
+// `collab/tests/persistence_test` once that backend is available.
+#[cfg(test)]
+mod tests {
+    use super::should_compact;
+
+    #[test]
+    fn compacts_once_threshold_is_reached() {
+        assert!(!should_compact(499, 500));
+        assert!(should_compact(500, 500));
+        assert!(should_compact(501, 500));
+    }
+
+    #[test]
+    fn threshold_is_configurable() {
+        assert!(!should_compact(5, 10));
+        assert!(should_compact(10, 10));
     }
 }
@@ -0,0 +1,379 @@
+use crate::core::collab_plugin::CollabPlugin;
+use crate::error::CollabError;
+
+use collab_persistence::CollabKV;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use yrs::types::ToJson;
+use yrs::{ReadTxn, TransactionMut};
+
+/// BM25 tuning constants, using the same defaults as the reference formula
+/// (`k1 = 1.2`, `b = 0.75`).
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const SNIPPET_LEN: usize = 80;
+
+const SEARCH_INDEX_KV_KEY: &[u8] = b"search_index_v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posting {
+    cid: String,
+    block_id: String,
+    term_frequency: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SearchIndexState {
+    /// term -> postings of (cid, block_id) that contain it.
+    postings: HashMap<String, Vec<Posting>>,
+    /// "{cid}/{block_id}" -> token count, used as `docLen` in BM25.
+    doc_lengths: HashMap<String, u32>,
+    /// "{cid}/{block_id}" -> raw text, used to build result snippets.
+    texts: HashMap<String, String>,
+}
+
+pub struct SearchHit {
+    pub cid: String,
+    pub block_id: String,
+    pub snippet: String,
+    pub score: f64,
+}
+
+/// A [CollabPlugin] that tokenizes the text content of `Document`s and the
+/// view/workspace names of the folder module into an inverted index, and
+/// ranks queries against it with BM25, so documents and views can be found
+/// by content rather than only by id.
+pub struct SearchIndexPlugin {
+    db: Arc<CollabKV>,
+    state: Arc<RwLock<SearchIndexState>>,
+}
+
+impl SearchIndexPlugin {
+    pub fn new(db: Arc<CollabKV>) -> Result<Self, CollabError> {
+        let state = db
+            .get(SEARCH_INDEX_KV_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            db,
+            state: Arc::new(RwLock::new(state)),
+        })
+    }
+
+    /// Ranks every indexed block against `query`, using prefix matching on
+    /// indexed terms so the result list can update as the user types.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        rank(&self.state.read(), query, limit)
+    }
+
+    fn reindex_document(&self, cid: &str, txn: &TransactionMut) {
+        let Some(attrs) = txn.get_map("attrs") else {
+            return;
+        };
+        let mut leaves = vec![];
+        collect_strings(&[], None, &attrs.to_json(txn), &mut leaves);
+
+        let mut state = self.state.write();
+        for postings in state.postings.values_mut() {
+            postings.retain(|posting| posting.cid != cid);
+        }
+        state.postings.retain(|_, postings| !postings.is_empty());
+        let prefix = format!("{cid}/");
+        state.doc_lengths.retain(|key, _| !key.starts_with(&prefix));
+        state.texts.retain(|key, _| !key.starts_with(&prefix));
+
+        for (block_id, text, term_frequencies) in group_leaves_by_block(leaves) {
+            let doc_key = format!("{cid}/{block_id}");
+            let doc_len = term_frequencies.values().sum::<u32>();
+            state.doc_lengths.insert(doc_key.clone(), doc_len);
+            state.texts.insert(doc_key, text);
+
+            for (term, term_frequency) in term_frequencies {
+                state.postings.entry(term).or_default().push(Posting {
+                    cid: cid.to_string(),
+                    block_id: block_id.clone(),
+                    term_frequency,
+                });
+            }
+        }
+
+        if let Ok(bytes) = serde_json::to_vec(&*state) {
+            let _ = self.db.set(SEARCH_INDEX_KV_KEY, &bytes);
+        }
+    }
+}
+
+/// Groups `(block_id, text)` leaves by `block_id`, since an entity (a map
+/// with its own `"id"` field) can carry more than one string leaf — e.g. a
+/// block's `data` map with both `delta` and `align`. Concatenating the text
+/// and summing term frequencies here keeps `doc_lengths`/`texts` in sync with
+/// every leaf's contribution to the postings, instead of the last leaf
+/// silently overwriting the ones before it.
+fn group_leaves_by_block(
+    leaves: Vec<(String, String)>,
+) -> Vec<(String, String, HashMap<String, u32>)> {
+    let mut texts: HashMap<String, String> = HashMap::new();
+    let mut term_frequencies: HashMap<String, HashMap<String, u32>> = HashMap::new();
+
+    for (block_id, text) in leaves {
+        for term in tokenize(&text) {
+            *term_frequencies
+                .entry(block_id.clone())
+                .or_default()
+                .entry(term)
+                .or_insert(0) += 1;
+        }
+        let entry = texts.entry(block_id).or_default();
+        if !entry.is_empty() {
+            entry.push(' ');
+        }
+        entry.push_str(&text);
+    }
+
+    texts
+        .into_iter()
+        .map(|(block_id, text)| {
+            let term_frequencies = term_frequencies.remove(&block_id).unwrap_or_default();
+            (block_id, text, term_frequencies)
+        })
+        .collect()
+}
+
+impl CollabPlugin for SearchIndexPlugin {
+    fn did_receive_update(&self, cid: &str, txn: &TransactionMut, _update: &[u8]) {
+        self.reindex_document(cid, txn);
+    }
+}
+
+/// BM25-ranks every indexed block in `state` against `query`. Pulled out of
+/// [SearchIndexPlugin::search] so the scoring math can be exercised against
+/// a hand-built [SearchIndexState] without a real `CollabKV`.
+fn rank(state: &SearchIndexState, query: &str, limit: usize) -> Vec<SearchHit> {
+    let query_terms = tokenize(query);
+    if query_terms.is_empty() || state.doc_lengths.is_empty() {
+        return vec![];
+    }
+
+    let doc_count = state.doc_lengths.len() as f64;
+    let avg_doc_len =
+        state.doc_lengths.values().sum::<u32>() as f64 / state.doc_lengths.len() as f64;
+
+    let mut scores: HashMap<(String, String), f64> = HashMap::new();
+    for query_term in &query_terms {
+        for (term, postings) in state.postings.iter() {
+            if term != query_term && !term.starts_with(query_term.as_str()) {
+                continue;
+            }
+            let doc_frequency = postings.len() as f64;
+            let idf = ((doc_count - doc_frequency + 0.5) / (doc_frequency + 0.5) + 1.0).ln();
+            for posting in postings {
+                let doc_key = format!("{}/{}", posting.cid, posting.block_id);
+                let doc_len = *state.doc_lengths.get(&doc_key).unwrap_or(&1) as f64;
+                let tf = posting.term_frequency as f64;
+                let score = idf * (tf * (K1 + 1.0))
+                    / (tf + K1 * (1.0 - B + B * doc_len / avg_doc_len));
+                *scores
+                    .entry((posting.cid.clone(), posting.block_id.clone()))
+                    .or_insert(0.0) += score;
+            }
+        }
+    }
+
+    let mut hits: Vec<SearchHit> = scores
+        .into_iter()
+        .map(|((cid, block_id), score)| {
+            let doc_key = format!("{cid}/{block_id}");
+            let snippet = state
+                .texts
+                .get(&doc_key)
+                .map(|text| snippet_of(text))
+                .unwrap_or_default();
+            SearchHit {
+                cid,
+                block_id,
+                snippet,
+                score,
+            }
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    hits.truncate(limit);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with(docs: &[(&str, &str, &str)]) -> SearchIndexState {
+        let mut state = SearchIndexState::default();
+        for (cid, block_id, text) in docs {
+            let doc_key = format!("{cid}/{block_id}");
+            let terms = tokenize(text);
+            state.doc_lengths.insert(doc_key.clone(), terms.len() as u32);
+            state.texts.insert(doc_key, text.to_string());
+
+            let mut term_frequencies: HashMap<String, u32> = HashMap::new();
+            for term in terms {
+                *term_frequencies.entry(term).or_insert(0) += 1;
+            }
+            for (term, term_frequency) in term_frequencies {
+                state.postings.entry(term).or_default().push(Posting {
+                    cid: cid.to_string(),
+                    block_id: block_id.to_string(),
+                    term_frequency,
+                });
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn ranks_documents_with_more_term_matches_higher() {
+        let state = state_with(&[
+            ("doc1", "block1", "the quick brown fox"),
+            ("doc2", "block1", "the quick quick quick fox jumps"),
+        ]);
+
+        let hits = rank(&state, "quick", 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].cid, "doc2");
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn supports_prefix_matching_for_incremental_typing() {
+        let state = state_with(&[("doc1", "block1", "collaboration is fun")]);
+        let hits = rank(&state, "collab", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].cid, "doc1");
+    }
+
+    #[test]
+    fn collect_strings_attributes_text_to_entity_id_not_field_name() {
+        let view_json: lib0::any::Any = serde_json::from_value(serde_json::json!({
+            "id": "view-1",
+            "name": "My first view",
+        }))
+        .unwrap();
+
+        let mut out = vec![];
+        collect_strings(&[], None, &view_json, &mut out);
+
+        assert_eq!(out, vec![("view-1".to_string(), "My first view".to_string())]);
+    }
+
+    #[test]
+    fn group_leaves_by_block_accumulates_all_leaves_of_one_entity() {
+        let leaves = vec![
+            ("block-1".to_string(), "hello world".to_string()),
+            ("block-1".to_string(), "left".to_string()),
+        ];
+
+        let mut grouped = group_leaves_by_block(leaves);
+        assert_eq!(grouped.len(), 1);
+        let (block_id, text, term_frequencies) = grouped.remove(0);
+
+        assert_eq!(block_id, "block-1");
+        assert_eq!(text, "hello world left");
+        assert_eq!(
+            term_frequencies.values().sum::<u32>(),
+            3,
+            "doc length should reflect tokens from every leaf, not just the last one"
+        );
+        assert_eq!(term_frequencies.get("hello"), Some(&1));
+        assert_eq!(term_frequencies.get("left"), Some(&1));
+    }
+
+    #[test]
+    fn collect_strings_keeps_sibling_entities_distinct() {
+        let views_json: lib0::any::Any = serde_json::from_value(serde_json::json!([
+            { "id": "view-1", "name": "First" },
+            { "id": "view-2", "name": "Second" },
+        ]))
+        .unwrap();
+
+        let mut out = vec![];
+        collect_strings(&[], None, &views_json, &mut out);
+        out.sort();
+
+        assert_eq!(
+            out,
+            vec![
+                ("view-1".to_string(), "First".to_string()),
+                ("view-2".to_string(), "Second".to_string()),
+            ]
+        );
+    }
+}
+
+/// Walks a decoded `attributes` tree and collects `(entity_id, text)` pairs
+/// for every string leaf. A map that carries its own `"id"` field (as
+/// `Workspace`/`View` records do) becomes the entity for everything nested
+/// under it, so e.g. a view's `name` is indexed under the view's id rather
+/// than under the literal JSON key `"name"`. Maps with no `id` field (like
+/// `TextMap`'s container, keyed directly by text id) fall back to the
+/// nearest JSON key, preserving the previous behavior for text nodes.
+fn collect_strings(
+    path: &[String],
+    entity_id: Option<&str>,
+    any: &lib0::any::Any,
+    out: &mut Vec<(String, String)>,
+) {
+    match any {
+        lib0::any::Any::String(text) => {
+            let block_id = entity_id.map(str::to_string).or_else(|| path.last().cloned());
+            if let Some(block_id) = block_id {
+                out.push((block_id, text.to_string()));
+            }
+        }
+        lib0::any::Any::Map(map) => {
+            let own_id = map.get("id").and_then(as_str);
+            let effective_id = own_id.as_deref().or(entity_id);
+            for (key, value) in map.iter() {
+                if key == "id" {
+                    continue;
+                }
+                let mut child_path = path.to_vec();
+                child_path.push(key.clone());
+                collect_strings(&child_path, effective_id, value, out);
+            }
+        }
+        lib0::any::Any::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                let mut child_path = path.to_vec();
+                child_path.push(index.to_string());
+                collect_strings(&child_path, entity_id, value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn as_str(any: &lib0::any::Any) -> Option<String> {
+    match any {
+        lib0::any::Any::String(s) => Some(s.to_string()),
+        _ => None,
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn snippet_of(text: &str) -> String {
+    if text.chars().count() <= SNIPPET_LEN {
+        text.to_string()
+    } else {
+        let snippet: String = text.chars().take(SNIPPET_LEN).collect();
+        format!("{snippet}…")
+    }
+}
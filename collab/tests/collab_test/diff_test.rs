@@ -0,0 +1,70 @@
+use collab::preclude::*;
+use yrs::{ArrayPrelim, ReadTxn, Transact};
+
+#[test]
+fn diff_reports_nested_insert_update_and_delete() {
+    let collab = CollabBuilder::new(1, "1").build();
+    collab.with_transact_mut(|txn| {
+        let person = collab.create_map_with_txn(txn, "person");
+        person.insert(txn, "name", "Alice");
+        person.insert(txn, "age", 30);
+    });
+
+    let old_snapshot = collab.transact().snapshot();
+
+    collab.with_transact_mut(|txn| {
+        let person = collab.get_map_with_txn(txn, vec!["person"]).unwrap();
+        person.insert(txn, "age", 31);
+        person.remove(txn, "name");
+        person.insert(txn, "email", "alice@example.com");
+    });
+
+    let txn = collab.transact();
+    let patches = collab.diff_with_txn(&txn, &old_snapshot);
+
+    let has_update_age = patches.iter().any(|p| {
+        matches!(p, CollabPatch::Update { path, new, .. } if path == &vec!["person".to_string(), "age".to_string()] && new == &serde_json::json!(31))
+    });
+    let has_delete_name = patches.iter().any(|p| {
+        matches!(p, CollabPatch::Delete { path } if path == &vec!["person".to_string(), "name".to_string()])
+    });
+    let has_insert_email = patches.iter().any(|p| {
+        matches!(p, CollabPatch::Insert { path, .. } if path == &vec!["person".to_string(), "email".to_string()])
+    });
+
+    assert!(has_update_age, "expected an Update patch for person/age, got {patches:?}");
+    assert!(has_delete_name, "expected a Delete patch for person/name, got {patches:?}");
+    assert!(has_insert_email, "expected an Insert patch for person/email, got {patches:?}");
+}
+
+#[test]
+fn diff_reports_fine_grained_array_changes() {
+    let collab = CollabBuilder::new(1, "1").build();
+    collab.with_transact_mut(|txn| {
+        collab.insert_with_txn(txn, "tags", ArrayPrelim::from(["a", "b", "c"]));
+    });
+
+    let old_snapshot = collab.transact().snapshot();
+
+    collab.with_transact_mut(|txn| {
+        let tags = collab.get_array_with_txn(txn, vec!["tags"]).unwrap();
+        tags.remove(txn, 2);
+        tags.push_back(txn, "d");
+    });
+
+    let txn = collab.transact();
+    let patches = collab.diff_with_txn(&txn, &old_snapshot);
+
+    assert!(
+        patches
+            .iter()
+            .any(|p| matches!(p, CollabPatch::Update { path, old, new } if path == &vec!["tags".to_string(), "2".to_string()] && old == &serde_json::json!("c") && new == &serde_json::json!("d"))),
+        "expected an Update patch at tags/2 from \"c\" to \"d\", got {patches:?}"
+    );
+    assert!(
+        !patches
+            .iter()
+            .any(|p| matches!(p, CollabPatch::Delete { .. } | CollabPatch::Insert { .. })),
+        "an element replaced at the same index should be reported as an Update only, got {patches:?}"
+    );
+}
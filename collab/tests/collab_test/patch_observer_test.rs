@@ -0,0 +1,55 @@
+use std::sync::{Arc, Mutex};
+
+use collab::preclude::*;
+use yrs::ArrayPrelim;
+
+#[test]
+fn array_retain_delete_insert_reports_patches_in_post_deletion_index_space() {
+    let mut collab = CollabBuilder::new(1, "1").build();
+    collab.with_transact_mut(|txn| {
+        collab.insert_with_txn(txn, "tags", ArrayPrelim::from(["a", "b", "c", "d"]));
+    });
+
+    let patches = Arc::new(Mutex::new(vec![]));
+    let patches_clone = patches.clone();
+    let _subscription = collab.observe_changes(move |new_patches| {
+        patches_clone.lock().unwrap().extend(new_patches);
+    });
+
+    collab.with_transact_mut(|txn| {
+        let tags = collab.get_array_with_txn(txn, vec!["tags"]).unwrap();
+        // Retain "a", remove "b" and "c", insert "x" in their place.
+        tags.remove_range(txn, 1, 2);
+        tags.insert(txn, 1, "x");
+    });
+
+    let patches = patches.lock().unwrap();
+    let deletes: Vec<_> = patches
+        .iter()
+        .filter_map(|p| match p {
+            CollabPatch::Delete { path } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    let inserts: Vec<_> = patches
+        .iter()
+        .filter_map(|p| match p {
+            CollabPatch::Insert { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        deletes,
+        vec![
+            vec!["tags".to_string(), "1".to_string()],
+            vec!["tags".to_string(), "1".to_string()],
+        ],
+        "both removed elements should be reported at the post-deletion index, got {patches:?}"
+    );
+    assert_eq!(
+        inserts,
+        vec![vec!["tags".to_string(), "1".to_string()]],
+        "the inserted element should land at the index the deletes vacated, got {patches:?}"
+    );
+}
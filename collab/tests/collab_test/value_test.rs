@@ -0,0 +1,69 @@
+use collab::preclude::*;
+
+#[test]
+fn get_value_and_get_typed_round_trip_a_map() {
+    let mut collab = CollabBuilder::new(1, "1").build();
+    collab
+        .insert_json_with_path(vec![], "person", serde_json::json!({ "name": "Alice", "age": 30 }))
+        .unwrap();
+
+    let value = collab.get_value_with_path(vec!["person"]).unwrap();
+    assert!(matches!(value, CollabValue::Map(_)));
+
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct Person {
+        name: String,
+        age: i64,
+    }
+    let person: Person = collab.get_typed_with_path(vec!["person"]).unwrap();
+    assert_eq!(
+        person,
+        Person {
+            name: "Alice".to_string(),
+            age: 30,
+        }
+    );
+}
+
+#[test]
+fn get_typed_with_path_reports_path_not_found_for_an_absent_path() {
+    let collab = CollabBuilder::new(1, "1").build();
+    let err = collab.get_typed_with_path::<serde_json::Value>(vec!["missing"]).unwrap_err();
+    assert!(matches!(err, CollabError::PathNotFound(_)));
+}
+
+#[test]
+fn schema_accepts_a_write_matching_the_declared_shape() {
+    let mut collab = CollabBuilder::new(1, "1")
+        .with_schema(
+            vec!["person".to_string()],
+            serde_json::json!({ "name": null, "age": null }),
+        )
+        .build();
+
+    let result = collab.insert_json_with_path(
+        vec![],
+        "person",
+        serde_json::json!({ "name": "Alice", "age": 30 }),
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn schema_rejects_a_write_missing_a_required_field_with_missing_field_not_path_not_found() {
+    let mut collab = CollabBuilder::new(1, "1")
+        .with_schema(
+            vec!["person".to_string()],
+            serde_json::json!({ "name": null, "age": null }),
+        )
+        .build();
+
+    let err = collab
+        .insert_json_with_path(vec![], "person", serde_json::json!({ "name": "Alice" }))
+        .unwrap_err();
+
+    assert!(
+        matches!(err, CollabError::MissingField(_)),
+        "a schema-rejected write should not be reported as CollabError::PathNotFound, got {err:?}"
+    );
+}